@@ -0,0 +1,214 @@
+//! # algorithm
+//!
+//! Default algorithm lists and modifier resolution for algorithm-list ssh_config
+//! keywords (`Ciphers`, `MACs`, `CASignatureAlgorithms`, `PubkeyAcceptedAlgorithms`).
+
+/**
+ * MIT License
+ *
+ * ssh2-config - Copyright (c) 2021 Christian Visintin
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use crate::glob;
+
+/// Default ciphers supported by libssh2, in order of preference
+pub(crate) const DEFAULT_CIPHERS: &[&str] = &[
+    "chacha20-poly1305@openssh.com",
+    "aes256-gcm@openssh.com",
+    "aes128-gcm@openssh.com",
+    "aes256-ctr",
+    "aes192-ctr",
+    "aes128-ctr",
+];
+
+/// Default MAC algorithms supported by libssh2, in order of preference
+pub(crate) const DEFAULT_MACS: &[&str] = &[
+    "hmac-sha2-256-etm@openssh.com",
+    "hmac-sha2-512-etm@openssh.com",
+    "hmac-sha2-256",
+    "hmac-sha2-512",
+    "hmac-sha1",
+];
+
+/// Default certificate authority signature algorithms supported by libssh2, in order of
+/// preference
+pub(crate) const DEFAULT_CA_SIGNATURE_ALGORITHMS: &[&str] = &[
+    "ssh-ed25519",
+    "ecdsa-sha2-nistp256",
+    "ecdsa-sha2-nistp384",
+    "ecdsa-sha2-nistp521",
+    "rsa-sha2-512",
+    "rsa-sha2-256",
+    "ssh-rsa",
+];
+
+/// Default public key signature algorithms accepted by libssh2 for pubkey authentication, in
+/// order of preference
+pub(crate) const DEFAULT_PUBKEY_ACCEPTED_ALGORITHMS: &[&str] = &[
+    "ssh-ed25519",
+    "ecdsa-sha2-nistp256",
+    "ecdsa-sha2-nistp384",
+    "ecdsa-sha2-nistp521",
+    "rsa-sha2-512",
+    "rsa-sha2-256",
+    "ssh-rsa",
+];
+
+/// An algorithm list that has been resolved against a default list, honouring the OpenSSH
+/// `+`/`-`/`^` modifier prefixes. The only way to construct one is [`resolve`], so a
+/// `HostParams` algorithm field can never end up holding an unresolved raw value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlgorithmList(Vec<String>);
+
+impl AlgorithmList {
+    /// The resolved algorithm names, in preference order
+    pub fn as_slice(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Consumes this list, returning the resolved algorithm names
+    pub fn into_vec(self) -> Vec<String> {
+        self.0
+    }
+}
+
+impl std::ops::Deref for AlgorithmList {
+    type Target = [String];
+
+    fn deref(&self) -> &[String] {
+        &self.0
+    }
+}
+
+/// Resolves a raw, comma-separated ssh_config algorithm-list value against `defaults`,
+/// honouring the OpenSSH `+`, `-` and `^` modifier prefixes.
+///
+/// - No prefix: the list entirely replaces `defaults`
+/// - `+list`: `list` is appended to `defaults`
+/// - `-list`: entries in `defaults` matching any (possibly wildcarded) pattern in `list` are
+///   removed; a pattern that matches nothing is a no-op
+/// - `^list`: `list` is moved to the head of `defaults`
+///
+/// The result is de-duplicated, keeping the first occurrence of each entry.
+pub(crate) fn resolve(defaults: &[&str], raw: &str) -> AlgorithmList {
+    let raw = raw.trim();
+    let resolved = if let Some(rest) = raw.strip_prefix('+') {
+        defaults
+            .iter()
+            .map(|s| s.to_string())
+            .chain(split_list(rest))
+            .collect()
+    } else if let Some(rest) = raw.strip_prefix('-') {
+        let patterns: Vec<String> = split_list(rest).collect();
+        defaults
+            .iter()
+            .map(|s| s.to_string())
+            .filter(|item| !patterns.iter().any(|pattern| glob::matches(pattern, item)))
+            .collect()
+    } else if let Some(rest) = raw.strip_prefix('^') {
+        split_list(rest)
+            .chain(defaults.iter().map(|s| s.to_string()))
+            .collect()
+    } else {
+        split_list(raw).collect()
+    };
+
+    AlgorithmList(dedup(resolved))
+}
+
+/// Splits a comma-separated algorithm list, trimming whitespace around each entry
+fn split_list(raw: &str) -> impl Iterator<Item = String> + '_ {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// De-duplicates `items`, keeping the first occurrence of each value
+fn dedup(items: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    items
+        .into_iter()
+        .filter(|item| seen.insert(item.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_replace_defaults() {
+        let result = resolve(&["a", "b", "c"], "x,y").into_vec();
+        assert_eq!(result, vec!["x".to_string(), "y".to_string()]);
+    }
+
+    #[test]
+    fn should_append_to_defaults() {
+        let result = resolve(&["a", "b"], "+c,d").into_vec();
+        assert_eq!(
+            result,
+            vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string(),
+                "d".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn should_prepend_to_defaults() {
+        let result = resolve(&["a", "b"], "^c,d").into_vec();
+        assert_eq!(
+            result,
+            vec![
+                "c".to_string(),
+                "d".to_string(),
+                "a".to_string(),
+                "b".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn should_remove_from_defaults() {
+        let result = resolve(&["hmac-sha1", "hmac-sha2-256", "aes256-ctr"], "-hmac-*").into_vec();
+        assert_eq!(result, vec!["aes256-ctr".to_string()]);
+    }
+
+    #[test]
+    fn should_be_noop_when_removal_pattern_matches_nothing() {
+        let result = resolve(&["a", "b"], "-z").into_vec();
+        assert_eq!(result, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn should_dedup_keeping_first_occurrence() {
+        let result = resolve(&["a", "b"], "+a,c").into_vec();
+        assert_eq!(
+            result,
+            vec!["a".to_string(), "b".to_string(), "c".to_string()]
+        );
+    }
+}
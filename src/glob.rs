@@ -0,0 +1,97 @@
+//! # glob
+//!
+//! Shared shell-style glob matching (`*` and `?` wildcards), used by every ssh_config keyword
+//! that matches patterns against text: `Match` criteria, algorithm-list removal
+//! patterns, and `Include` file globs. Callers differ only in whether the match should be
+//! case-sensitive.
+
+/**
+ * MIT License
+ *
+ * ssh2-config - Copyright (c) 2021 Christian Visintin
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+/// Matches `text` against a shell-style glob `pattern` (`*` and `?` wildcards), comparing
+/// bytes with `eq`
+fn matches_by(pattern: &[u8], text: &[u8], eq: impl Fn(u8, u8) -> bool + Copy) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            matches_by(&pattern[1..], text, eq)
+                || (!text.is_empty() && matches_by(pattern, &text[1..], eq))
+        }
+        (Some(b'?'), Some(_)) => matches_by(&pattern[1..], &text[1..], eq),
+        (Some(p), Some(t)) if eq(*p, *t) => matches_by(&pattern[1..], &text[1..], eq),
+        _ => false,
+    }
+}
+
+/// Matches `text` against `pattern`, treating letters as case-sensitive
+pub(crate) fn matches(pattern: &str, text: &str) -> bool {
+    matches_by(pattern.as_bytes(), text.as_bytes(), |a, b| a == b)
+}
+
+/// Matches `text` against `pattern`, treating ASCII letters as case-insensitive
+pub(crate) fn matches_ignore_ascii_case(pattern: &str, text: &str) -> bool {
+    matches_by(pattern.as_bytes(), text.as_bytes(), |a, b| {
+        a.eq_ignore_ascii_case(&b)
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_match_exact() {
+        assert!(matches("abc", "abc"));
+        assert!(!matches("abc", "abd"));
+    }
+
+    #[test]
+    fn should_match_star_wildcard() {
+        assert!(matches("*.example.com", "foo.example.com"));
+        assert!(matches("hmac-*", "hmac-sha1"));
+        assert!(!matches("hmac-*", "aes256-ctr"));
+    }
+
+    #[test]
+    fn should_match_question_mark_wildcard() {
+        assert!(matches("192.168.1.?", "192.168.1.1"));
+        assert!(!matches("192.168.1.?", "192.168.1.12"));
+    }
+
+    #[test]
+    fn should_be_case_sensitive_by_default() {
+        assert!(!matches("ABC", "abc"));
+    }
+
+    #[test]
+    fn should_ignore_ascii_case_when_requested() {
+        assert_eq!(matches_ignore_ascii_case("ABC", "abc"), true);
+        assert_eq!(
+            matches_ignore_ascii_case("*.EXAMPLE.com", "foo.example.com"),
+            true
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! # include
+//!
+//! Implements the plumbing behind the `Include` directive: glob expansion of the included
+//! path relative to the including file, and cycle/depth guards around the recursion. The
+//! actual line-by-line parsing of an included file is supplied by the caller (the config
+//! parser), so that included files are merged through the same `HostParams::merge_defaults`
+//! path used for `Host`/`Match` blocks.
+
+/**
+ * MIT License
+ *
+ * ssh2-config - Copyright (c) 2021 Christian Visintin
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::glob;
+use crate::params::HostParams;
+
+/// The maximum depth of nested `Include` directives before giving up, matching the guard
+/// OpenSSH itself applies against runaway recursion
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Tracks the currently active chain of included files, to detect cycles, and the current
+/// recursion depth, to cap runaway nesting
+#[derive(Debug, Default)]
+pub struct IncludeGuard {
+    chain: HashSet<PathBuf>,
+    depth: usize,
+}
+
+impl IncludeGuard {
+    /// Creates a new, empty guard
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks `path` as being included. Fails if `path` is already part of the current
+    /// inclusion chain (a cycle) or if the maximum include depth has been reached.
+    fn enter(&mut self, path: &Path) -> Result<(), String> {
+        if self.depth >= MAX_INCLUDE_DEPTH {
+            return Err(format!(
+                "maximum include depth ({MAX_INCLUDE_DEPTH}) exceeded while including {}",
+                path.display()
+            ));
+        }
+
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if !self.chain.insert(canonical) {
+            return Err(format!("include cycle detected at {}", path.display()));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Marks `path` as no longer being included, once it (and everything it transitively
+    /// includes) has finished parsing
+    fn exit(&mut self, path: &Path) {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.chain.remove(&canonical);
+        self.depth = self.depth.saturating_sub(1);
+    }
+}
+
+/// Expands an `Include` argument into the concrete, sorted list of files it refers to.
+///
+/// A relative pattern is resolved against `including_dir` (the directory of the file
+/// containing the `Include` line); a pattern starting with `~` is resolved against
+/// `home_dir` instead, matching `ssh`'s own behaviour.
+pub fn expand_include_pattern(
+    pattern: &str,
+    including_dir: &Path,
+    home_dir: &Path,
+) -> Vec<PathBuf> {
+    let resolved = if let Some(rest) = pattern.strip_prefix('~') {
+        home_dir.join(rest.strip_prefix('/').unwrap_or(rest))
+    } else if Path::new(pattern).is_absolute() {
+        PathBuf::from(pattern)
+    } else {
+        including_dir.join(pattern)
+    };
+
+    let dir = resolved.parent().unwrap_or_else(|| Path::new("."));
+    let file_pattern = resolved
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    if !file_pattern.contains('*') && !file_pattern.contains('?') {
+        return vec![resolved];
+    }
+
+    let mut matches: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .map(|name| glob::matches(&file_pattern, &name.to_string_lossy()))
+                        .unwrap_or(false)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    matches.sort();
+    matches
+}
+
+/// Resolves an `Include` directive: expands `pattern` to the files it matches and parses
+/// each one (via `parse_file`, recursing into nested `Include` directives as needed),
+/// merging the results in file order using `HostParams::merge_defaults` so that, consistent
+/// with the surrounding `Host`/`Match` precedence, the first value encountered wins.
+pub fn resolve_include(
+    pattern: &str,
+    including_dir: &Path,
+    home_dir: &Path,
+    guard: &mut IncludeGuard,
+    mut parse_file: impl FnMut(&Path, &mut IncludeGuard) -> Result<HostParams, String>,
+) -> Result<HostParams, String> {
+    let mut result = HostParams::default();
+
+    for path in expand_include_pattern(pattern, including_dir, home_dir) {
+        guard.enter(&path)?;
+        let parsed = parse_file(&path, guard);
+        guard.exit(&path);
+        result.merge_defaults(&parsed?);
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "ssh2-config-include-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn should_resolve_plain_relative_path() {
+        let dir = temp_dir("plain");
+        let expanded = expand_include_pattern("config.d/extra", &dir, &dir);
+        assert_eq!(expanded, vec![dir.join("config.d/extra")]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_resolve_tilde_path() {
+        let dir = temp_dir("tilde");
+        let home = temp_dir("tilde-home");
+        let expanded = expand_include_pattern("~/config.d/extra", &dir, &home);
+        assert_eq!(expanded, vec![home.join("config.d/extra")]);
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn should_expand_glob_pattern_sorted() {
+        let dir = temp_dir("glob");
+        let config_d = dir.join("config.d");
+        std::fs::create_dir_all(&config_d).unwrap();
+        std::fs::write(config_d.join("20-b.conf"), "").unwrap();
+        std::fs::write(config_d.join("10-a.conf"), "").unwrap();
+        std::fs::write(config_d.join("readme.txt"), "").unwrap();
+
+        let expanded = expand_include_pattern("config.d/*.conf", &dir, &dir);
+        assert_eq!(
+            expanded,
+            vec![config_d.join("10-a.conf"), config_d.join("20-b.conf")]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_merge_included_files_first_value_wins() {
+        let dir = temp_dir("merge");
+        std::fs::write(dir.join("10-a.conf"), "").unwrap();
+        std::fs::write(dir.join("20-b.conf"), "").unwrap();
+        let mut guard = IncludeGuard::new();
+
+        let result = resolve_include("*.conf", &dir, &dir, &mut guard, |path, _guard| {
+            let mut params = HostParams::default();
+            if path.file_name().unwrap() == "10-a.conf" {
+                params.host_name = Some("from-a".to_string());
+            } else {
+                params.host_name = Some("from-b".to_string());
+                params.compression = Some(true);
+            }
+            Ok(params)
+        })
+        .unwrap();
+
+        assert_eq!(result.host_name.unwrap(), "from-a");
+        assert_eq!(result.compression.unwrap(), true);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_detect_include_cycle() {
+        let dir = temp_dir("cycle");
+        let file = dir.join("loop.conf");
+        std::fs::write(&file, "").unwrap();
+        let mut guard = IncludeGuard::new();
+
+        let result = resolve_include("loop.conf", &dir, &dir, &mut guard, |_path, guard| {
+            // the included file itself includes the same file back: a cycle
+            resolve_include("loop.conf", &dir, &dir, guard, |_, _| {
+                Ok(HostParams::default())
+            })
+        });
+
+        assert!(result.is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_cap_recursion_depth() {
+        let dir = temp_dir("depth");
+        let mut guard = IncludeGuard::new();
+        for i in 0..MAX_INCLUDE_DEPTH {
+            let file = dir.join(format!("{i}.conf"));
+            std::fs::write(&file, "").unwrap();
+            guard.enter(&file).unwrap();
+        }
+        let overflow = dir.join("overflow.conf");
+        std::fs::write(&overflow, "").unwrap();
+        assert!(guard.enter(&overflow).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,47 @@
+//! # ssh2-config
+//!
+//! ssh2-config is a library which provides the host parameters of the ssh configuration
+//! file, together with the helpers to resolve them (algorithm-list modifiers, `Match`
+//! blocks, `ProxyJump` values, `Include` expansion, `%`-token expansion), in order to make
+//! easier to create ssh2 sessions. Reading and tokenizing a config file is not done here.
+
+/**
+ * MIT License
+ *
+ * ssh2-config - Copyright (c) 2021 Christian Visintin
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+mod algorithm;
+mod glob;
+mod include;
+mod match_pattern;
+mod params;
+mod proxy;
+mod token;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub use self::algorithm::AlgorithmList;
+pub use self::include::{resolve_include, IncludeGuard};
+pub use self::match_pattern::{MatchBlock, MatchQuery};
+pub use self::params::HostParams;
+pub use self::proxy::{ProxyJump, ProxyJumpHop};
+pub use self::token::ExpansionContext;
@@ -0,0 +1,286 @@
+//! # match_pattern
+//!
+//! Implements the `Match` directive, which conditionally applies a block of ssh_config
+//! keywords based on criteria evaluated against the connection target, as opposed to the
+//! static glob matching performed by `Host`.
+
+/**
+ * MIT License
+ *
+ * ssh2-config - Copyright (c) 2021 Christian Visintin
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use std::process::Command;
+
+use crate::glob;
+use crate::params::HostParams;
+
+/// The query context a `Match` block is evaluated against, supplied by the caller once the
+/// connection target is known
+#[derive(Debug, Clone, Default)]
+pub struct MatchQuery {
+    /// The target host, as it will be resolved (i.e. after `HostName` substitution)
+    pub host: String,
+    /// The original host name, as typed on the command line / by the caller, before any
+    /// `HostName` substitution
+    pub original_host: String,
+    /// The resolved remote user
+    pub user: Option<String>,
+}
+
+/// A single criterion of a `Match` line (e.g. `host <pat>`, `!user <name>`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MatchCriterion {
+    All,
+    Host(String),
+    User(String),
+    OriginalHost(String),
+    Exec(String),
+}
+
+/// A parsed `Match` directive: an AND of (possibly negated) criteria, plus the `HostParams`
+/// parsed from the keyword lines that follow the `Match` line in the config file
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MatchBlock {
+    criteria: Vec<(bool, MatchCriterion)>,
+    params: HostParams,
+}
+
+impl MatchBlock {
+    /// Parses the argument of a `Match` line (everything after the `Match` keyword) into a
+    /// `MatchBlock`
+    pub fn parse(args: &str) -> Result<Self, String> {
+        let mut tokens = args.split_whitespace();
+        let mut criteria = Vec::new();
+
+        while let Some(token) = tokens.next() {
+            let (negate, keyword) = match token.strip_prefix('!') {
+                Some(keyword) => (true, keyword),
+                None => (false, token),
+            };
+
+            if keyword.eq_ignore_ascii_case("all") {
+                criteria.push((negate, MatchCriterion::All));
+                continue;
+            }
+
+            let criterion = match keyword.to_ascii_lowercase().as_str() {
+                "host" => MatchCriterion::Host(Self::next_arg(&mut tokens, "host")?),
+                "user" => MatchCriterion::User(Self::next_arg(&mut tokens, "user")?),
+                "originalhost" => {
+                    MatchCriterion::OriginalHost(Self::next_arg(&mut tokens, "originalhost")?)
+                }
+                "exec" => MatchCriterion::Exec(Self::next_arg(&mut tokens, "exec")?),
+                other => return Err(format!("unsupported match criterion: {other}")),
+            };
+
+            criteria.push((negate, criterion));
+        }
+
+        Ok(Self {
+            criteria,
+            params: HostParams::default(),
+        })
+    }
+
+    /// Attaches the `HostParams` parsed from the keyword lines following this `Match` line
+    pub fn with_params(mut self, params: HostParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// The `HostParams` parsed from the keyword lines following this `Match` line
+    pub fn params(&self) -> &HostParams {
+        &self.params
+    }
+
+    /// If this block matches `query`, merges its params into `target` following the
+    /// `HostParams::merge_defaults` first-value-wins precedence. Returns whether it matched.
+    pub fn apply(&self, query: &MatchQuery, target: &mut HostParams) -> bool {
+        let matched = self.is_match(query);
+        if matched {
+            target.merge_defaults(&self.params);
+        }
+        matched
+    }
+
+    fn next_arg<'a>(
+        tokens: &mut std::str::SplitWhitespace<'a>,
+        keyword: &str,
+    ) -> Result<String, String> {
+        tokens
+            .next()
+            .map(str::to_string)
+            .ok_or_else(|| format!("missing argument for match criterion `{keyword}`"))
+    }
+
+    /// Returns whether every criterion in this block matches `query` (AND semantics)
+    pub fn is_match(&self, query: &MatchQuery) -> bool {
+        self.criteria
+            .iter()
+            .all(|(negate, criterion)| Self::criterion_matches(criterion, query) != *negate)
+    }
+
+    fn criterion_matches(criterion: &MatchCriterion, query: &MatchQuery) -> bool {
+        match criterion {
+            MatchCriterion::All => true,
+            MatchCriterion::Host(pattern) => glob::matches_ignore_ascii_case(pattern, &query.host),
+            MatchCriterion::OriginalHost(pattern) => {
+                glob::matches_ignore_ascii_case(pattern, &query.original_host)
+            }
+            MatchCriterion::User(pattern) => query
+                .user
+                .as_deref()
+                .is_some_and(|user| glob::matches_ignore_ascii_case(pattern, user)),
+            MatchCriterion::Exec(command) => run_exec(command),
+        }
+    }
+}
+
+/// Runs `command` through the shell, treating a zero exit status as a match
+fn run_exec(command: &str) -> bool {
+    Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_parse_match_all() {
+        let block = MatchBlock::parse("all").unwrap();
+        assert_eq!(block.criteria, vec![(false, MatchCriterion::All)]);
+    }
+
+    #[test]
+    fn should_parse_multiple_criteria() {
+        let block = MatchBlock::parse("host *.example.com user root").unwrap();
+        assert_eq!(
+            block.criteria,
+            vec![
+                (false, MatchCriterion::Host("*.example.com".to_string())),
+                (false, MatchCriterion::User("root".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_negated_criterion() {
+        let block = MatchBlock::parse("!host *.internal").unwrap();
+        assert_eq!(
+            block.criteria,
+            vec![(true, MatchCriterion::Host("*.internal".to_string()))]
+        );
+    }
+
+    #[test]
+    fn should_match_host_glob() {
+        let block = MatchBlock::parse("host *.example.com").unwrap();
+        let query = MatchQuery {
+            host: "foo.example.com".to_string(),
+            ..Default::default()
+        };
+        assert!(block.is_match(&query));
+    }
+
+    #[test]
+    fn should_honor_negation() {
+        let block = MatchBlock::parse("!host *.internal").unwrap();
+        let query = MatchQuery {
+            host: "foo.example.com".to_string(),
+            ..Default::default()
+        };
+        assert!(block.is_match(&query));
+
+        let query = MatchQuery {
+            host: "foo.internal".to_string(),
+            ..Default::default()
+        };
+        assert!(!block.is_match(&query));
+    }
+
+    #[test]
+    fn should_apply_params_into_target_when_matched() {
+        let mut params = HostParams::default();
+        params.host_name = Some("192.168.1.2".to_string());
+        let block = MatchBlock::parse("host *.example.com")
+            .unwrap()
+            .with_params(params);
+        let query = MatchQuery {
+            host: "foo.example.com".to_string(),
+            ..Default::default()
+        };
+
+        let mut target = HostParams::default();
+        assert!(block.apply(&query, &mut target));
+        assert_eq!(target.host_name.unwrap(), "192.168.1.2");
+    }
+
+    #[test]
+    fn should_not_apply_params_into_target_when_not_matched() {
+        let mut params = HostParams::default();
+        params.host_name = Some("192.168.1.2".to_string());
+        let block = MatchBlock::parse("host *.internal")
+            .unwrap()
+            .with_params(params);
+        let query = MatchQuery {
+            host: "foo.example.com".to_string(),
+            ..Default::default()
+        };
+
+        let mut target = HostParams::default();
+        assert!(!block.apply(&query, &mut target));
+        assert!(target.host_name.is_none());
+    }
+
+    #[test]
+    fn should_require_all_criteria_to_match() {
+        let block = MatchBlock::parse("host *.example.com user root").unwrap();
+        let query = MatchQuery {
+            host: "foo.example.com".to_string(),
+            user: Some("nobody".to_string()),
+            ..Default::default()
+        };
+        assert!(!block.is_match(&query));
+    }
+
+    #[test]
+    fn should_match_exec() {
+        let block = MatchBlock::parse("exec true").unwrap();
+        assert!(block.is_match(&MatchQuery::default()));
+
+        let block = MatchBlock::parse("exec false").unwrap();
+        assert!(!block.is_match(&MatchQuery::default()));
+    }
+
+    #[test]
+    fn should_fail_on_unknown_criterion() {
+        assert!(MatchBlock::parse("canonical").is_err());
+    }
+}
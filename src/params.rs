@@ -26,6 +26,9 @@
  * SOFTWARE.
  */
 use super::{Duration, PathBuf};
+use crate::algorithm::{self, AlgorithmList};
+use crate::proxy::ProxyJump;
+use crate::token::ExpansionContext;
 
 /// Describes the ssh configuration.
 /// Configuration is describes in this document: <http://man.openbsd.org/OpenBSD-current/man5/ssh_config.5>
@@ -36,12 +39,18 @@ pub struct HostParams {
     pub bind_address: Option<String>,
     /// Use the specified address on the local machine as the source address of the connection
     pub bind_interface: Option<String>,
-    /// Specifies which algorithms are allowed for signing of certificates by certificate authorities
-    pub ca_signature_algorithms: Option<Vec<String>>,
+    /// Specifies which algorithms are allowed for signing of certificates by certificate authorities.
+    /// Obtained by resolving a raw `CASignatureAlgorithms` value through
+    /// [`HostParams::resolve_ca_signature_algorithms`], which applies the `+`/`-`/`^` modifiers
+    /// against the default list; [`AlgorithmList`] cannot be constructed any other way.
+    pub ca_signature_algorithms: Option<AlgorithmList>,
     /// Specifies a file from which the user's certificate is read
     pub certificate_file: Option<PathBuf>,
-    /// Specifies the ciphers allowed for protocol version 2 in order of preference
-    pub ciphers: Option<Vec<String>>,
+    /// Specifies the ciphers allowed for protocol version 2 in order of preference.
+    /// Obtained by resolving a raw `Ciphers` value through [`HostParams::resolve_ciphers`], which
+    /// applies the `+`/`-`/`^` modifiers against the default list; [`AlgorithmList`] cannot be
+    /// constructed any other way.
+    pub ciphers: Option<AlgorithmList>,
     /// Specifies whether to use compression
     pub compression: Option<bool>,
     /// Specifies the number of attempts to make before exiting
@@ -50,12 +59,24 @@ pub struct HostParams {
     pub connect_timeout: Option<Duration>,
     /// Specifies the real host name to log into
     pub host_name: Option<String>,
-    /// Specifies the MAC (message authentication code) algorithms in order of preference
-    pub mac: Option<Vec<String>>,
-    /// Specifies the signature algorithms that will be used for public key authentication
-    pub pubkey_accepted_algorithms: Option<Vec<String>>,
+    /// Specifies the MAC (message authentication code) algorithms in order of preference.
+    /// Obtained by resolving a raw `MACs` value through [`HostParams::resolve_mac`], which
+    /// applies the `+`/`-`/`^` modifiers against the default list; [`AlgorithmList`] cannot be
+    /// constructed any other way.
+    pub mac: Option<AlgorithmList>,
+    /// Specifies the signature algorithms that will be used for public key authentication.
+    /// Obtained by resolving a raw `PubkeyAcceptedAlgorithms` value through
+    /// [`HostParams::resolve_pubkey_accepted_algorithms`], which applies the `+`/`-`/`^`
+    /// modifiers against the default list; [`AlgorithmList`] cannot be constructed any other way.
+    pub pubkey_accepted_algorithms: Option<AlgorithmList>,
     /// Specifies whether to try public key authentication using SSH keys
     pub pubkey_authentication: Option<bool>,
+    /// Specifies a command to use to connect to the server, instead of opening a direct TCP
+    /// connection
+    pub proxy_command: Option<String>,
+    /// Specifies one or more jump hosts to tunnel the connection through, or `none` to disable
+    /// jumping
+    pub proxy_jump: Option<ProxyJump>,
     /// Specifies that a TCP port on the remote machine be forwarded over the secure channel
     pub remote_forward: Option<u16>,
     /// Specifies whether to send TCP keepalives to the other side
@@ -101,6 +122,12 @@ impl HostParams {
         if let Some(pubkey_authentication) = b.pubkey_authentication {
             self.pubkey_authentication = Some(pubkey_authentication);
         }
+        if let Some(proxy_command) = b.proxy_command.clone() {
+            self.proxy_command = Some(proxy_command);
+        }
+        if let Some(proxy_jump) = b.proxy_jump.clone() {
+            self.proxy_jump = Some(proxy_jump);
+        }
         if let Some(remote_forward) = b.remote_forward {
             self.remote_forward = Some(remote_forward);
         }
@@ -108,9 +135,102 @@ impl HostParams {
             self.tcp_keep_alive = Some(tcp_keep_alive);
         }
     }
+
+    /// Merges `b` into `self` following OpenSSH's "first obtained value wins" semantics: a
+    /// field already set on `self` is left untouched, and only fields currently `None` are
+    /// filled in from `b`.
+    ///
+    /// This is the rule that must be used when resolving a host configuration: matched
+    /// `Host`/`Match` blocks are merged in file order via repeated calls to this method,
+    /// followed by the global `Host *` defaults, so that the first block to set a value wins
+    /// over later, less specific ones.
+    pub fn merge_defaults(&mut self, b: &Self) {
+        if self.bind_address.is_none() {
+            self.bind_address = b.bind_address.clone();
+        }
+        if self.bind_interface.is_none() {
+            self.bind_interface = b.bind_interface.clone();
+        }
+        if self.ca_signature_algorithms.is_none() {
+            self.ca_signature_algorithms = b.ca_signature_algorithms.clone();
+        }
+        if self.certificate_file.is_none() {
+            self.certificate_file = b.certificate_file.clone();
+        }
+        if self.ciphers.is_none() {
+            self.ciphers = b.ciphers.clone();
+        }
+        if self.compression.is_none() {
+            self.compression = b.compression;
+        }
+        if self.connection_attemps.is_none() {
+            self.connection_attemps = b.connection_attemps;
+        }
+        if self.connect_timeout.is_none() {
+            self.connect_timeout = b.connect_timeout;
+        }
+        if self.host_name.is_none() {
+            self.host_name = b.host_name.clone();
+        }
+        if self.mac.is_none() {
+            self.mac = b.mac.clone();
+        }
+        if self.pubkey_accepted_algorithms.is_none() {
+            self.pubkey_accepted_algorithms = b.pubkey_accepted_algorithms.clone();
+        }
+        if self.pubkey_authentication.is_none() {
+            self.pubkey_authentication = b.pubkey_authentication;
+        }
+        if self.proxy_command.is_none() {
+            self.proxy_command = b.proxy_command.clone();
+        }
+        if self.proxy_jump.is_none() {
+            self.proxy_jump = b.proxy_jump.clone();
+        }
+        if self.remote_forward.is_none() {
+            self.remote_forward = b.remote_forward;
+        }
+        if self.tcp_keep_alive.is_none() {
+            self.tcp_keep_alive = b.tcp_keep_alive;
+        }
+    }
+
+    /// Resolves a raw `Ciphers` value against the libssh2 default cipher list, honouring the
+    /// `+`, `-` and `^` modifier prefixes
+    pub fn resolve_ciphers(raw: &str) -> AlgorithmList {
+        algorithm::resolve(algorithm::DEFAULT_CIPHERS, raw)
+    }
+
+    /// Resolves a raw `MACs` value against the libssh2 default MAC list, honouring the `+`, `-`
+    /// and `^` modifier prefixes
+    pub fn resolve_mac(raw: &str) -> AlgorithmList {
+        algorithm::resolve(algorithm::DEFAULT_MACS, raw)
+    }
+
+    /// Resolves a raw `CASignatureAlgorithms` value against the libssh2 default CA signature
+    /// algorithm list, honouring the `+`, `-` and `^` modifier prefixes
+    pub fn resolve_ca_signature_algorithms(raw: &str) -> AlgorithmList {
+        algorithm::resolve(algorithm::DEFAULT_CA_SIGNATURE_ALGORITHMS, raw)
+    }
+
+    /// Resolves a raw `PubkeyAcceptedAlgorithms` value against the libssh2 default pubkey
+    /// algorithm list, honouring the `+`, `-` and `^` modifier prefixes
+    pub fn resolve_pubkey_accepted_algorithms(raw: &str) -> AlgorithmList {
+        algorithm::resolve(algorithm::DEFAULT_PUBKEY_ACCEPTED_ALGORITHMS, raw)
+    }
+
+    /// Returns `certificate_file` with `%`-tokens and a leading `~` expanded against `ctx`.
+    /// Expansion is deferred to this method, rather than done at parse time, since the
+    /// connection target (and therefore the expanded value) isn't known until then.
+    pub fn certificate_file(&self, ctx: &ExpansionContext) -> Option<PathBuf> {
+        self.certificate_file
+            .as_ref()
+            .map(|raw| ctx.expand_path(&raw.to_string_lossy()))
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod test {
 
     use super::*;
@@ -132,6 +252,8 @@ mod test {
         assert!(params.mac.is_none());
         assert!(params.pubkey_accepted_algorithms.is_none());
         assert!(params.pubkey_authentication.is_none());
+        assert!(params.proxy_command.is_none());
+        assert!(params.proxy_jump.is_none());
         assert!(params.remote_forward.is_none());
         assert!(params.tcp_keep_alive.is_none());
     }
@@ -142,16 +264,18 @@ mod test {
         let mut b = HostParams::default();
         b.bind_address = Some(String::from("pippo"));
         b.bind_interface = Some(String::from("tun0"));
-        b.ca_signature_algorithms = Some(vec![]);
+        b.ca_signature_algorithms = Some(HostParams::resolve_ca_signature_algorithms(""));
         b.certificate_file = Some(PathBuf::default());
-        b.ciphers = Some(vec![]);
+        b.ciphers = Some(HostParams::resolve_ciphers(""));
         b.compression = Some(true);
         b.connect_timeout = Some(Duration::from_secs(1));
         b.connection_attemps = Some(3);
         b.host_name = Some(String::from("192.168.1.2"));
-        b.mac = Some(vec![]);
-        b.pubkey_accepted_algorithms = Some(vec![]);
+        b.mac = Some(HostParams::resolve_mac(""));
+        b.pubkey_accepted_algorithms = Some(HostParams::resolve_pubkey_accepted_algorithms(""));
         b.pubkey_authentication = Some(true);
+        b.proxy_command = Some(String::from("nc %h %p"));
+        b.proxy_jump = Some(ProxyJump::None);
         b.remote_forward = Some(32);
         b.tcp_keep_alive = Some(true);
         params.merge(&b);
@@ -167,6 +291,8 @@ mod test {
         assert!(params.mac.is_some());
         assert!(params.pubkey_accepted_algorithms.is_some());
         assert!(params.pubkey_authentication.is_some());
+        assert!(params.proxy_command.is_some());
+        assert!(params.proxy_jump.is_some());
         assert!(params.remote_forward.is_some());
         assert!(params.tcp_keep_alive.is_some());
         // merge twices
@@ -174,4 +300,62 @@ mod test {
         params.merge(&b);
         assert_eq!(params.tcp_keep_alive.unwrap(), true);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_merge_defaults_without_overwriting_already_set_fields() {
+        let mut params = HostParams::default();
+        params.host_name = Some(String::from("192.168.1.1"));
+        let mut defaults = HostParams::default();
+        defaults.host_name = Some(String::from("192.168.1.2"));
+        defaults.bind_address = Some(String::from("pippo"));
+        defaults.tcp_keep_alive = Some(true);
+
+        params.merge_defaults(&defaults);
+
+        // already set on `self`: untouched
+        assert_eq!(params.host_name.unwrap(), "192.168.1.1");
+        // unset on `self`: filled from defaults
+        assert_eq!(params.bind_address.unwrap(), "pippo");
+        assert_eq!(params.tcp_keep_alive.unwrap(), true);
+    }
+
+    #[test]
+    fn should_expand_certificate_file() {
+        let mut params = HostParams::default();
+        params.certificate_file = Some(PathBuf::from("~/.ssh/%h-cert.pub"));
+        let ctx = ExpansionContext {
+            host: String::from("example.com"),
+            original_host: String::from("example"),
+            port: 22,
+            remote_user: None,
+            local_user: String::from("pippo"),
+            home_dir: PathBuf::from("/home/pippo"),
+        };
+
+        assert_eq!(
+            params.certificate_file(&ctx).unwrap(),
+            PathBuf::from("/home/pippo/.ssh/example.com-cert.pub")
+        );
+    }
+
+    #[test]
+    fn should_parse_proxy_jump_chain_into_params() {
+        let mut params = HostParams::default();
+        params.proxy_jump = Some(ProxyJump::parse("a@bastion1:22,bastion2").unwrap());
+        assert_eq!(
+            params.proxy_jump.unwrap(),
+            ProxyJump::Hops(vec![
+                crate::proxy::ProxyJumpHop {
+                    user: Some("a".to_string()),
+                    host: "bastion1".to_string(),
+                    port: Some(22),
+                },
+                crate::proxy::ProxyJumpHop {
+                    user: None,
+                    host: "bastion2".to_string(),
+                    port: None,
+                },
+            ])
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! # proxy
+//!
+//! Models the `ProxyJump` directive, used to tunnel a connection through one or more bastion
+//! hosts.
+
+/**
+ * MIT License
+ *
+ * ssh2-config - Copyright (c) 2021 Christian Visintin
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+/// A single `[user@]host[:port]` hop of a `ProxyJump` chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyJumpHop {
+    /// The user to authenticate as on this hop, if specified
+    pub user: Option<String>,
+    /// The jump host
+    pub host: String,
+    /// The port to connect to on this hop, if specified
+    pub port: Option<u16>,
+}
+
+/// The resolved value of a `ProxyJump` directive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxyJump {
+    /// `ProxyJump none`: explicitly disables jumping, overriding any earlier configuration
+    None,
+    /// An ordered chain of hops to tunnel through, in the order they must be connected to
+    Hops(Vec<ProxyJumpHop>),
+}
+
+impl ProxyJump {
+    /// Parses a `ProxyJump` value, which is either the literal `none` or a comma-separated
+    /// list of `[user@]host[:port]` hops
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        if raw.eq_ignore_ascii_case("none") {
+            return Ok(Self::None);
+        }
+
+        raw.split(',')
+            .map(str::trim)
+            .filter(|hop| !hop.is_empty())
+            .map(parse_hop)
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::Hops)
+    }
+}
+
+fn parse_hop(raw: &str) -> Result<ProxyJumpHop, String> {
+    let (user, rest) = match raw.split_once('@') {
+        Some((user, rest)) => (Some(user.to_string()), rest),
+        None => (None, raw),
+    };
+
+    let (host, port) = match rest.split_once(':') {
+        Some((host, port)) => {
+            let port = port
+                .parse::<u16>()
+                .map_err(|_| format!("invalid port in proxy jump hop: {raw}"))?;
+            (host, Some(port))
+        }
+        None => (rest, None),
+    };
+
+    if host.is_empty() {
+        return Err(format!("missing host in proxy jump hop: {raw}"));
+    }
+
+    Ok(ProxyJumpHop {
+        user,
+        host: host.to_string(),
+        port,
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_parse_none() {
+        assert_eq!(ProxyJump::parse("none").unwrap(), ProxyJump::None);
+        assert_eq!(ProxyJump::parse("None").unwrap(), ProxyJump::None);
+    }
+
+    #[test]
+    fn should_parse_single_hop() {
+        let parsed = ProxyJump::parse("bastion.example.com").unwrap();
+        assert_eq!(
+            parsed,
+            ProxyJump::Hops(vec![ProxyJumpHop {
+                user: None,
+                host: "bastion.example.com".to_string(),
+                port: None,
+            }])
+        );
+    }
+
+    #[test]
+    fn should_parse_hop_with_user_and_port() {
+        let parsed = ProxyJump::parse("root@bastion.example.com:2222").unwrap();
+        assert_eq!(
+            parsed,
+            ProxyJump::Hops(vec![ProxyJumpHop {
+                user: Some("root".to_string()),
+                host: "bastion.example.com".to_string(),
+                port: Some(2222),
+            }])
+        );
+    }
+
+    #[test]
+    fn should_parse_multi_hop_chain() {
+        let parsed = ProxyJump::parse("a@one:22,two,root@three:2022").unwrap();
+        assert_eq!(
+            parsed,
+            ProxyJump::Hops(vec![
+                ProxyJumpHop {
+                    user: Some("a".to_string()),
+                    host: "one".to_string(),
+                    port: Some(22),
+                },
+                ProxyJumpHop {
+                    user: None,
+                    host: "two".to_string(),
+                    port: None,
+                },
+                ProxyJumpHop {
+                    user: Some("root".to_string()),
+                    host: "three".to_string(),
+                    port: Some(2022),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn should_fail_on_invalid_port() {
+        assert!(ProxyJump::parse("host:notaport").is_err());
+    }
+}
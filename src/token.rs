@@ -0,0 +1,154 @@
+//! # token
+//!
+//! Implements ssh_config's `%`-token expansion (`%h`, `%p`, `%r`, `%n`, `%d`, `%u`, `%%`) and
+//! `~` expansion for path/command fields whose final value can only be known once the
+//! connection target is resolved.
+
+/**
+ * MIT License
+ *
+ * ssh2-config - Copyright (c) 2021 Christian Visintin
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::PathBuf;
+
+/// Context supplied by the caller, once the connection target is known, to resolve `%`-tokens
+/// and `~` in path/command fields
+#[derive(Debug, Clone)]
+pub struct ExpansionContext {
+    /// The target host (`%h`)
+    pub host: String,
+    /// The original host name, as typed, before `HostName` substitution (`%n`)
+    pub original_host: String,
+    /// The port to connect to (`%p`)
+    pub port: u16,
+    /// The remote user (`%r`)
+    pub remote_user: Option<String>,
+    /// The local user running the client (`%u`)
+    pub local_user: String,
+    /// The local user's home directory, used both for `%d` and for `~` expansion
+    pub home_dir: PathBuf,
+}
+
+impl ExpansionContext {
+    /// Expands `%`-tokens in `raw` against this context
+    fn expand_tokens(&self, raw: &str) -> String {
+        let mut out = String::with_capacity(raw.len());
+        let mut chars = raw.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+
+            match chars.next() {
+                Some('h') => out.push_str(&self.host),
+                Some('p') => out.push_str(&self.port.to_string()),
+                Some('r') => out.push_str(self.remote_user.as_deref().unwrap_or_default()),
+                Some('n') => out.push_str(&self.original_host),
+                Some('d') => out.push_str(&self.home_dir.to_string_lossy()),
+                Some('u') => out.push_str(&self.local_user),
+                Some('%') => out.push('%'),
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+                None => out.push('%'),
+            }
+        }
+
+        out
+    }
+
+    /// Expands a leading `~` to this context's home directory
+    fn expand_tilde(&self, raw: &str) -> PathBuf {
+        match raw.strip_prefix('~') {
+            Some(rest) => {
+                let rest = rest.strip_prefix('/').unwrap_or(rest);
+                self.home_dir.join(rest)
+            }
+            None => PathBuf::from(raw),
+        }
+    }
+
+    /// Expands `%`-tokens and a leading `~` in `raw`, returning the concrete path
+    pub(crate) fn expand_path(&self, raw: &str) -> PathBuf {
+        self.expand_tilde(&self.expand_tokens(raw))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn ctx() -> ExpansionContext {
+        ExpansionContext {
+            host: "example.com".to_string(),
+            original_host: "example".to_string(),
+            port: 22,
+            remote_user: Some("root".to_string()),
+            local_user: "pippo".to_string(),
+            home_dir: PathBuf::from("/home/pippo"),
+        }
+    }
+
+    #[test]
+    fn should_expand_tokens() {
+        let expanded = ctx().expand_tokens("%h:%p %r %n %u %%");
+        assert_eq!(expanded, "example.com:22 root example pippo %");
+    }
+
+    #[test]
+    fn should_expand_home_token() {
+        let expanded = ctx().expand_tokens("%d/.ssh");
+        assert_eq!(expanded, "/home/pippo/.ssh");
+    }
+
+    #[test]
+    fn should_leave_unknown_sequences_untouched() {
+        let expanded = ctx().expand_tokens("%x");
+        assert_eq!(expanded, "%x");
+    }
+
+    #[test]
+    fn should_expand_tilde() {
+        let expanded = ctx().expand_path("~/.ssh/id_rsa");
+        assert_eq!(expanded, PathBuf::from("/home/pippo/.ssh/id_rsa"));
+    }
+
+    #[test]
+    fn should_expand_tilde_and_tokens_together() {
+        let expanded = ctx().expand_path("~/.ssh/%h-cert.pub");
+        assert_eq!(
+            expanded,
+            PathBuf::from("/home/pippo/.ssh/example.com-cert.pub")
+        );
+    }
+
+    #[test]
+    fn should_leave_absolute_path_without_tilde_untouched() {
+        let expanded = ctx().expand_path("/etc/ssh/%h-cert.pub");
+        assert_eq!(expanded, PathBuf::from("/etc/ssh/example.com-cert.pub"));
+    }
+}